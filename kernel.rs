@@ -1,8 +1,138 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::fmt;
 
 // --- 1. Core Kernel Definitions ---
 
+/// Identifies an external event (e.g. a disk I/O completion) that tasks
+/// can block on and the kernel can later signal.
+type EventId = u32;
+
+/// The event Task 3's simulated disk read blocks on.
+const DISK_IO_EVENT: EventId = 1;
+
+/// How many ticks after a `Block` the simulated I/O completes and the
+/// kernel delivers the matching `Signal`.
+const IO_COMPLETION_DELAY: u32 = 4;
+
+/// Number of priority levels in the multi-level feedback queue.
+/// Level 0 is the highest priority (shortest slice); level NUM_LEVELS - 1
+/// is the lowest (longest slice).
+const NUM_LEVELS: usize = 8;
+
+/// How often (in ticks) the scheduler performs a priority boost, moving
+/// every task back to level 0 so long-running tasks can't starve newer ones.
+const BOOST_INTERVAL: u32 = 10;
+
+/// How many tasks a CPU pulls from the shared injector queue at once.
+const INJECTOR_BATCH: usize = 2;
+
+/// Number of worker CPUs in the SMP simulation.
+const NUM_CPUS: usize = 2;
+
+/// Real-time tasks don't participate in MLFQ time-slicing; give them an
+/// effectively unbounded slice so slice expiry never takes them off the
+/// CPU, only a kernel call of their own (such as `Exit` at period end).
+const RT_SLICE: u32 = u32::MAX;
+
+/// Number of buckets in the kernel's sleep timer wheel.
+const TIMER_WHEEL_SIZE: usize = 16;
+
+/// The time slice (in ticks) granted to a task at a given MLFQ level.
+/// Doubles with each level: 1, 2, 4, 8, ...
+fn slice_for_level(level: u8) -> u32 {
+    1u32 << level
+}
+
+/// Maps a task's `priority` (lower is more important, Unix-nice-style, on a
+/// 0-99 scale) onto its starting MLFQ level. This is what makes `priority`
+/// actually matter: a high-priority task still starts near level 0, while a
+/// low-priority one can be seeded further down the hierarchy from the outset,
+/// ahead of ever being demoted there by the feedback rule.
+fn initial_level_for_priority(priority: u8) -> u8 {
+    ((priority as usize * NUM_LEVELS) / 100).min(NUM_LEVELS - 1) as u8
+}
+
+/// Resets an unblocked task to the top MLFQ level. Per the feedback rule, a
+/// task coming back from I/O, a timer, or a `Recv` wait re-enters scheduling
+/// at level 0, not at whatever level it was demoted to before it blocked nor
+/// at the level its priority seeded when it was spawned.
+fn reset_to_top_level(task: &mut Process) {
+    task.level = 0;
+    task.slice_remaining = slice_for_level(0);
+}
+
+/// A tiny deterministic xorshift64 PRNG, used to pick which sibling core to
+/// steal from. Keeping the simulation's output reproducible matters more
+/// here than true randomness.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { state: seed | 1 }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 32) as u32
+    }
+}
+
+/// A hashed timer wheel for `Sleep` syscalls: sleeping tasks are bucketed by
+/// `wake_tick % TIMER_WHEEL_SIZE` so waking them each tick is an O(bucket
+/// size) lookup instead of a scan over every pending timer.
+struct TimerWheel {
+    buckets: [Vec<(u32, usize, Process)>; TIMER_WHEEL_SIZE], // (wake_tick, cpu_idx, task)
+}
+
+impl TimerWheel {
+    fn new() -> Self {
+        TimerWheel {
+            buckets: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+
+    /// Parks `task` (which was running on `cpu_idx`) until `wake_tick`.
+    fn schedule(&mut self, wake_tick: u32, cpu_idx: usize, task: Process) {
+        let bucket = wake_tick as usize % TIMER_WHEEL_SIZE;
+        self.buckets[bucket].push((wake_tick, cpu_idx, task));
+    }
+
+    /// Removes and returns every timer due at exactly `ticks`. A bucket can
+    /// still hold timers from a different lap of the wheel, so entries are
+    /// filtered by their exact wake tick rather than bucket membership alone.
+    fn pop_due(&mut self, ticks: u32) -> Vec<(usize, Process)> {
+        let bucket = &mut self.buckets[ticks as usize % TIMER_WHEEL_SIZE];
+        let mut due = Vec::new();
+        let mut remaining = Vec::new();
+        for (wake_tick, cpu_idx, task) in bucket.drain(..) {
+            if wake_tick == ticks {
+                due.push((cpu_idx, task));
+            } else {
+                remaining.push((wake_tick, cpu_idx, task));
+            }
+        }
+        *bucket = remaining;
+        due
+    }
+}
+
+/// Which scheduling discipline the kernel dispatches with. `Edf` lets
+/// real-time tasks (those with a `deadline`) preempt best-effort MLFQ tasks;
+/// `Mlfq` ignores real-time tasks entirely, for comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SchedPolicy {
+    Mlfq, // Best-effort only; used by this demo's comparison run against `Edf`
+    Edf,
+}
+
 /// Represents the possible states of a task/process managed by the kernel.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ProcessState {
@@ -19,199 +149,837 @@ struct Process {
     name: String,
     state: ProcessState,
     program_counter: u32, // Represents how far along the task is in its execution
-    priority: u8,         // Simple priority field (unused in this Round Robin example, but common)
+    priority: u8,          // Nice-style priority (lower = more important); seeds the initial level
+    level: u8,             // Current MLFQ run-queue level (0 = highest priority)
+    slice_remaining: u32,  // Ticks left in the task's current time slice
+    current_cpu: Option<u32>, // Which core last dispatched this task, if any
+    period: Option<u32>,   // Re-release interval, for periodic real-time tasks
+    deadline: Option<u32>, // Absolute tick by which this task must finish, if real-time
 }
 
 impl Process {
-    /// Creates a new, ready process.
+    /// Creates a new, ready process. Its starting MLFQ level is seeded from
+    /// `priority`, so a high-priority task begins at (or near) the top level
+    /// as is standard for a feedback queue, while a low-priority one can
+    /// start further down the hierarchy.
     fn new(id: u32, name: &str, priority: u8) -> Self {
+        let level = initial_level_for_priority(priority);
         Process {
             id,
             name: name.to_string(),
             state: ProcessState::Ready,
             program_counter: 0,
             priority,
+            level,
+            slice_remaining: slice_for_level(level),
+            current_cpu: None,
+            period: None,
+            deadline: None,
         }
     }
+
+    /// Creates a new real-time process with a period and an initial
+    /// absolute deadline. Real-time tasks are scheduled EDF-style, ahead of
+    /// everything in the MLFQ run-queues.
+    fn new_realtime(id: u32, name: &str, priority: u8, period: u32, deadline: u32) -> Self {
+        let mut task = Process::new(id, name, priority);
+        task.period = Some(period);
+        task.deadline = Some(deadline);
+        task.slice_remaining = RT_SLICE;
+        task
+    }
 }
 
 // Implement a custom display trait for nice printing
 impl fmt::Display for Process {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cpu = match self.current_cpu {
+            Some(cpu) => cpu.to_string(),
+            None => "-".to_string(),
+        };
+        let deadline = match self.deadline {
+            Some(d) => d.to_string(),
+            None => "-".to_string(),
+        };
         write!(
             f,
-            "[Task {}] ({}): State={:?}, PC={}",
-            self.id, self.name, self.state, self.program_counter
+            "[Task {}] ({}): State={:?}, PC={}, Priority={}, Level={}, CPU={}, Deadline={}",
+            self.id, self.name, self.state, self.program_counter, self.priority, self.level, cpu, deadline
         )
     }
 }
 
+/// A persistent accounting record for a process, kept around after the
+/// `Process` itself exits or is otherwise dropped so the kernel can report
+/// runtime statistics (turnaround time, wait time, etc.) at shutdown.
+struct ProcessControlBlock {
+    pid: u32,
+    name: String,
+    spawn_tick: u32,
+    exit_tick: Option<u32>,
+    cpu_ticks: u32,          // Cumulative ticks this task has actually run on a CPU
+    times_scheduled: u32,    // Number of times it was dispatched onto a CPU
+    voluntary_yields: u32,   // Number of times it called Yield
+    ticks_blocked: u32,      // Cumulative ticks spent in the Blocked state
+    blocked_since: Option<u32>, // Tick the current block started, if blocked now
+}
+
+impl ProcessControlBlock {
+    fn new(pid: u32, name: &str, spawn_tick: u32) -> Self {
+        ProcessControlBlock {
+            pid,
+            name: name.to_string(),
+            spawn_tick,
+            exit_tick: None,
+            cpu_ticks: 0,
+            times_scheduled: 0,
+            voluntary_yields: 0,
+            ticks_blocked: 0,
+            blocked_since: None,
+        }
+    }
+}
+
 /// System calls that a running process can make to request kernel services.
 #[derive(Debug)]
 enum KernelCall {
-    Yield,      // Relinquish the CPU to another task
-    Print(String), // Request the kernel to output a message
-    Exit,       // Terminate the process
-    Block,      // Go into a waiting state (e.g., waiting for I/O)
+    Yield,            // Relinquish the CPU to another task
+    Print(String),    // Request the kernel to output a message
+    Exit,             // Terminate the process
+    Block(EventId),   // Go into a waiting state until the named event fires
+    Signal(EventId),  // Wake every task waiting on the named event
+    Sleep(u32),       // Voluntarily suspend for the given number of ticks
+    Send(u32, String), // Deliver a message to the destination pid's mailbox
+    Recv,             // Receive the next message addressed to this task, blocking if none is queued
+}
+
+// --- 2. Per-CPU Scheduling ---
+
+/// One worker core in the SMP simulation. Each core owns its own MLFQ
+/// run-queues and only reaches into shared kernel state (the injector,
+/// sibling cores) when it runs dry.
+struct Cpu {
+    id: usize,
+    run_queues: [VecDeque<Process>; NUM_LEVELS],
+    rt_queue: VecDeque<Process>, // Real-time tasks, scheduled EDF-style ahead of run_queues
+    running_task: Option<Process>,
+    steals: u32,        // Tasks this core stole from a sibling
+    injector_pops: u32,  // Tasks this core pulled from the shared injector
+    idle_ticks: u32,     // Ticks this core had nothing to run
 }
 
-// --- 2. The Kernel Structure and Logic ---
+impl Cpu {
+    fn new(id: usize) -> Self {
+        Cpu {
+            id,
+            run_queues: std::array::from_fn(|_| VecDeque::new()),
+            rt_queue: VecDeque::new(),
+            running_task: None,
+            steals: 0,
+            injector_pops: 0,
+            idle_ticks: 0,
+        }
+    }
+
+    /// Whether this core has any task waiting in one of its local queues.
+    fn has_ready_tasks(&self) -> bool {
+        self.run_queues.iter().any(|q| !q.is_empty())
+    }
+
+    /// Every `BOOST_INTERVAL` ticks, move every waiting (and running) task on
+    /// this core back to level 0, preventing starvation at the bottom level.
+    fn maybe_priority_boost(&mut self, ticks: u32) {
+        if ticks == 0 || !ticks.is_multiple_of(BOOST_INTERVAL) {
+            return;
+        }
+        println!("[SCHEDULER] CPU {}: priority boost, resetting all tasks to level 0.", self.id);
+
+        for level in 1..NUM_LEVELS {
+            while let Some(mut task) = self.run_queues[level].pop_front() {
+                task.level = 0;
+                task.slice_remaining = slice_for_level(0);
+                self.run_queues[0].push_back(task);
+            }
+        }
+
+        if let Some(ref mut task) = self.running_task {
+            // RT tasks don't take part in MLFQ leveling; leave their
+            // effectively-unbounded RT_SLICE alone.
+            if task.deadline.is_none() {
+                task.level = 0;
+                task.slice_remaining = slice_for_level(0);
+            }
+        }
+    }
 
-/// The central structure managing all processes and scheduling.
+    /// Puts the currently running task back onto this core's queues, demoting
+    /// it a level if its time slice expired. Does not dispatch a replacement.
+    fn requeue_current(&mut self, demote: bool) {
+        if let Some(mut current_task) = self.running_task.take() {
+            // Only re-queue if the task is still running (i.e., it didn't call Exit/Block)
+            if current_task.state == ProcessState::Running {
+                current_task.state = ProcessState::Ready;
+                if current_task.deadline.is_some() {
+                    // Real-time tasks don't take part in MLFQ demotion; they
+                    // just wait for the EDF pick on this core's next dispatch.
+                    println!(
+                        "[SCHEDULER] CPU {}: RT task {} re-queued for EDF.",
+                        self.id, current_task.id
+                    );
+                    self.rt_queue.push_back(current_task);
+                    return;
+                }
+                if demote {
+                    if (current_task.level as usize) + 1 < NUM_LEVELS {
+                        current_task.level += 1;
+                    }
+                    println!(
+                        "[SCHEDULER] CPU {}: time slice ended for {}. Demoted to level {}.",
+                        self.id, current_task.id, current_task.level
+                    );
+                } else {
+                    println!(
+                        "[SCHEDULER] CPU {}: {} yielded the CPU. Level unchanged ({}).",
+                        self.id, current_task.id, current_task.level
+                    );
+                }
+                current_task.slice_remaining = slice_for_level(current_task.level);
+                self.run_queues[current_task.level as usize].push_back(current_task);
+            }
+        }
+    }
+
+    /// Picks the next task to run. Under `SchedPolicy::Edf`, the ready
+    /// real-time task with the earliest absolute deadline always wins;
+    /// non-real-time tasks only run in the slack when no RT task is ready.
+    /// Returns `false` (without touching shared state) if nothing is ready.
+    fn dispatch_local(&mut self, policy: SchedPolicy) -> bool {
+        if policy == SchedPolicy::Edf {
+            let earliest = self
+                .rt_queue
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, task)| task.deadline.unwrap_or(u32::MAX))
+                .map(|(idx, _)| idx);
+            if let Some(idx) = earliest {
+                let mut next_task = self.rt_queue.remove(idx).unwrap();
+                next_task.state = ProcessState::Running;
+                next_task.current_cpu = Some(self.id as u32);
+                println!("[SCHEDULER] CPU {} dispatching RT task: {}", self.id, next_task);
+                self.running_task = Some(next_task);
+                return true;
+            }
+        }
+
+        for queue in self.run_queues.iter_mut() {
+            if let Some(mut next_task) = queue.pop_front() {
+                next_task.state = ProcessState::Running;
+                next_task.current_cpu = Some(self.id as u32);
+                println!("[SCHEDULER] CPU {} dispatching: {}", self.id, next_task);
+                self.running_task = Some(next_task);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// --- 3. The Kernel Structure and Logic ---
+
+/// The central structure managing all processes and scheduling across CPUs.
 struct Kernel {
     next_pid: u32,
-    ready_queue: VecDeque<Process>, // Stores processes ready to run
-    running_task: Option<Process>,  // The task currently holding the CPU
-    ticks: u32,                     // Global timer for the simulation
+    cpus: Vec<Cpu>,
+    injector: VecDeque<Process>,              // Shared queue newly spawned tasks land on
+    ticks: u32,                                // Global timer for the simulation
+    blocked: HashMap<EventId, Vec<Process>>,   // Tasks parked on an event, keyed by event id
+    pending_signals: Vec<(u32, EventId)>,      // (fire_at_tick, event) timers for simulated I/O
+    rng: Rng,
+    process_table: HashMap<u32, ProcessControlBlock>, // Every task ever spawned, for accounting
+    sched_policy: SchedPolicy,
+    deadline_misses: u32,
+    rt_releases: Vec<(u32, usize, Process)>, // (release_tick, cpu_idx, task) sleeping until their next period
+    timer_wheel: TimerWheel,
+    mailboxes: HashMap<u32, VecDeque<String>>, // Per-pid inbox, delivered by Send and drained by Recv
+    recv_waiters: HashMap<u32, Process>,       // Tasks parked in Recv with an empty mailbox, keyed by their own pid
+    reported_misses: HashSet<(u32, u32)>,      // (pid, deadline) pairs already counted as a miss
 }
 
 impl Kernel {
-    /// Initializes a new kernel instance.
-    fn new() -> Self {
+    /// Initializes a new kernel instance with `num_cpus` worker cores under
+    /// the given scheduling policy.
+    fn new(num_cpus: usize, sched_policy: SchedPolicy) -> Self {
         Kernel {
             next_pid: 1,
-            ready_queue: VecDeque::new(),
-            running_task: None,
+            cpus: (0..num_cpus).map(Cpu::new).collect(),
+            injector: VecDeque::new(),
             ticks: 0,
+            blocked: HashMap::new(),
+            pending_signals: Vec::new(),
+            rng: Rng::new(0x2545_F491_4F6C_DD1D),
+            process_table: HashMap::new(),
+            sched_policy,
+            deadline_misses: 0,
+            rt_releases: Vec::new(),
+            timer_wheel: TimerWheel::new(),
+            mailboxes: HashMap::new(),
+            recv_waiters: HashMap::new(),
+            reported_misses: HashSet::new(),
         }
     }
 
-    /// Adds a new task to the kernel's management.
+    /// Adds a new task to the kernel. It lands on the shared injector queue
+    /// rather than any one core's run-queue, so whichever core goes idle
+    /// first picks it up.
     fn spawn_task(&mut self, name: &str, priority: u8) {
         let task = Process::new(self.next_pid, name, priority);
-        println!("[KERNEL] Spawning: {}", task);
-        self.ready_queue.push_back(task);
+        println!("[KERNEL] Spawning: {} onto the injector queue.", task);
+        self.process_table.insert(
+            task.id,
+            ProcessControlBlock::new(task.id, name, self.ticks),
+        );
+        self.mailboxes.insert(task.id, VecDeque::new());
+        self.injector.push_back(task);
         self.next_pid += 1;
     }
 
-    /// The core scheduling logic (Simple Round Robin).
-    fn schedule(&mut self) {
-        // If a task was running, check if it needs to be put back in the queue
-        if let Some(mut current_task) = self.running_task.take() {
-            // Only re-queue if the task is still running (i.e., it didn't call Exit)
-            if current_task.state == ProcessState::Running {
-                current_task.state = ProcessState::Ready;
-                println!(
-                    "[SCHEDULER] Time slice ended for {}. Re-queuing.",
-                    current_task.id
-                );
-                self.ready_queue.push_back(current_task);
+    /// Adds a new periodic real-time task, pinned to CPU 0's EDF queue.
+    /// Real-time tasks aren't stolen or load-balanced across cores, mirroring
+    /// the CPU affinity real RT schedulers give latency-sensitive work.
+    fn spawn_rt_task(&mut self, name: &str, priority: u8, period: u32) {
+        let deadline = self.ticks + period;
+        let task = Process::new_realtime(self.next_pid, name, priority, period, deadline);
+        println!("[KERNEL] Spawning RT task: {} onto CPU 0's EDF queue.", task);
+        self.process_table.insert(
+            task.id,
+            ProcessControlBlock::new(task.id, name, self.ticks),
+        );
+        self.mailboxes.insert(task.id, VecDeque::new());
+        self.cpus[0].rt_queue.push_back(task);
+        self.next_pid += 1;
+    }
+
+    /// Requeues the task running on `cpu_idx` (if any) and dispatches its
+    /// replacement: first from the core's own queues, then the injector,
+    /// then by stealing from a sibling core.
+    fn schedule(&mut self, cpu_idx: usize, demote: bool) {
+        self.cpus[cpu_idx].requeue_current(demote);
+        self.dispatch_next(cpu_idx);
+    }
+
+    /// Finds work for an idle core, in local -> injector -> steal order.
+    fn dispatch_next(&mut self, cpu_idx: usize) {
+        let policy = self.sched_policy;
+
+        if self.cpus[cpu_idx].dispatch_local(policy) {
+            self.record_dispatch(cpu_idx);
+            return;
+        }
+
+        if !self.injector.is_empty() {
+            let n = INJECTOR_BATCH.min(self.injector.len());
+            for _ in 0..n {
+                if let Some(task) = self.injector.pop_front() {
+                    self.cpus[cpu_idx].injector_pops += 1;
+                    self.cpus[cpu_idx].run_queues[task.level as usize].push_back(task);
+                }
+            }
+            if self.cpus[cpu_idx].dispatch_local(policy) {
+                self.record_dispatch(cpu_idx);
+                return;
             }
         }
 
-        // Pick the next task from the ready queue
-        if let Some(mut next_task) = self.ready_queue.pop_front() {
-            next_task.state = ProcessState::Running;
-            println!("[SCHEDULER] Dispatching: {}", next_task);
-            self.running_task = Some(next_task);
-        } else {
-            // If the ready queue is empty, the kernel is idle
-            println!("[SCHEDULER] Ready queue empty. Idling.");
+        if self.try_steal(cpu_idx) && self.cpus[cpu_idx].dispatch_local(policy) {
+            self.record_dispatch(cpu_idx);
+            return;
         }
+
+        self.cpus[cpu_idx].idle_ticks += 1;
+        println!("[SCHEDULER] CPU {} has no work. Idling.", cpu_idx);
+    }
+
+    /// Records in the process table that `cpu_idx`'s newly dispatched task
+    /// was just scheduled onto a CPU.
+    fn record_dispatch(&mut self, cpu_idx: usize) {
+        if let Some(task) = self.cpus[cpu_idx].running_task.as_ref() {
+            if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                pcb.times_scheduled += 1;
+            }
+        }
+    }
+
+    /// Attempts to steal half of a randomly chosen sibling core's queued
+    /// tasks. Tries every sibling in turn before giving up.
+    fn try_steal(&mut self, cpu_idx: usize) -> bool {
+        let num_cpus = self.cpus.len();
+        if num_cpus < 2 {
+            return false;
+        }
+        let start = self.rng.next_u32() as usize % num_cpus;
+        for offset in 0..num_cpus {
+            let victim_idx = (start + offset) % num_cpus;
+            if victim_idx == cpu_idx {
+                continue;
+            }
+            if self.steal_half(victim_idx, cpu_idx) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves half of `victim_idx`'s queued tasks onto `thief_idx`'s queues.
+    fn steal_half(&mut self, victim_idx: usize, thief_idx: usize) -> bool {
+        let mut victim_tasks: Vec<Process> = self.cpus[victim_idx]
+            .run_queues
+            .iter_mut()
+            .flat_map(|q| q.drain(..))
+            .collect();
+        if victim_tasks.is_empty() {
+            return false;
+        }
+
+        let steal_count = (victim_tasks.len() / 2).max(1);
+        let stolen: Vec<Process> = victim_tasks.drain(0..steal_count).collect();
+
+        // Put back what wasn't stolen.
+        for task in victim_tasks {
+            self.cpus[victim_idx].run_queues[task.level as usize].push_back(task);
+        }
+
+        println!(
+            "[SCHEDULER] CPU {} steals {} task(s) from CPU {}.",
+            thief_idx,
+            stolen.len(),
+            victim_idx
+        );
+        for task in stolen {
+            self.cpus[thief_idx].steals += 1;
+            self.cpus[thief_idx].run_queues[task.level as usize].push_back(task);
+        }
+        true
     }
 
-    /// Executes one time slice (one step) of the currently running task.
+    /// Wakes every task parked on `event_id`: moves it from `Blocked` back to
+    /// `Ready` and drops it on the shared injector for any idle core to pick up.
+    fn complete_event(&mut self, event_id: EventId) {
+        if let Some(waiters) = self.blocked.remove(&event_id) {
+            println!(
+                "[KERNEL] Event {} completed. Waking {} task(s) onto the injector.",
+                event_id,
+                waiters.len()
+            );
+            for mut task in waiters {
+                task.state = ProcessState::Ready;
+                reset_to_top_level(&mut task);
+                if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                    if let Some(since) = pcb.blocked_since.take() {
+                        pcb.ticks_blocked += self.ticks.saturating_sub(since);
+                    }
+                }
+                self.injector.push_back(task);
+            }
+        }
+    }
+
+    /// Delivers any simulated I/O completions whose timer has reached the
+    /// current tick, mimicking a reactor that resumes blocked work once its
+    /// external dependency is ready.
+    fn fire_due_signals(&mut self) {
+        let ticks = self.ticks;
+        let due: Vec<EventId> = self
+            .pending_signals
+            .iter()
+            .filter(|(fire_at, _)| *fire_at == ticks)
+            .map(|(_, event_id)| *event_id)
+            .collect();
+        self.pending_signals.retain(|(fire_at, _)| *fire_at != ticks);
+
+        for event_id in due {
+            println!(
+                "[KERNEL] I/O completion interrupt: signaling event {}.",
+                event_id
+            );
+            self.handle_kernel_call(0, KernelCall::Signal(event_id));
+        }
+    }
+
+    /// Wakes any task whose `Sleep` timer has reached its wake tick, putting
+    /// it back on the CPU it slept from (its MLFQ queue, or the EDF queue if
+    /// it's a real-time task).
+    fn wake_sleeping_tasks(&mut self) {
+        let ticks = self.ticks;
+        for (cpu_idx, mut task) in self.timer_wheel.pop_due(ticks) {
+            task.state = ProcessState::Ready;
+            if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                if let Some(since) = pcb.blocked_since.take() {
+                    pcb.ticks_blocked += ticks.saturating_sub(since);
+                }
+            }
+            println!("[KERNEL] Task {} WOKE from sleep (tick {}, CPU {}).", task.id, ticks, cpu_idx);
+            if task.deadline.is_some() {
+                self.cpus[cpu_idx].rt_queue.push_back(task);
+            } else {
+                reset_to_top_level(&mut task);
+                self.cpus[cpu_idx].run_queues[task.level as usize].push_back(task);
+            }
+        }
+    }
+
+    /// Wakes any real-time task whose next period has arrived, moving it
+    /// from its post-period sleep back onto its pinned CPU's EDF queue.
+    fn release_due_rt_tasks(&mut self) {
+        let ticks = self.ticks;
+        let due_indices: Vec<usize> = self
+            .rt_releases
+            .iter()
+            .enumerate()
+            .filter(|(_, (release_at, _, _))| *release_at == ticks)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        for idx in due_indices.into_iter().rev() {
+            let (_, cpu_idx, task) = self.rt_releases.remove(idx);
+            println!(
+                "[KERNEL] RT task {} released for its next period (deadline {}).",
+                task.id,
+                task.deadline.unwrap()
+            );
+            self.cpus[cpu_idx].rt_queue.push_back(task);
+        }
+    }
+
+    /// Checks every real-time task (running or still waiting) for a
+    /// deadline that the current tick has already passed, and counts/reports
+    /// each miss exactly once per (task, deadline) pair — a task stuck past
+    /// the same deadline for several ticks is still only one missed period.
+    fn check_deadline_misses(&mut self) {
+        let ticks = self.ticks;
+        let mut missed: Vec<(u32, u32)> = Vec::new();
+        for cpu in self.cpus.iter() {
+            if let Some(task) = cpu.running_task.as_ref() {
+                if let Some(d) = task.deadline.filter(|&d| ticks > d) {
+                    missed.push((task.id, d));
+                }
+            }
+            for task in cpu.rt_queue.iter() {
+                if let Some(d) = task.deadline.filter(|&d| ticks > d) {
+                    missed.push((task.id, d));
+                }
+            }
+        }
+        for (task_id, deadline) in missed {
+            if !self.reported_misses.insert((task_id, deadline)) {
+                continue;
+            }
+            self.deadline_misses += 1;
+            println!(
+                "[KERNEL] WARNING: RT task {} missed its deadline (tick {}).",
+                task_id, ticks
+            );
+        }
+    }
+
+    /// Whether any core is running a task or has MLFQ-queued work, the
+    /// injector has work, or anything is still in flight (blocked on an
+    /// event, asleep, or waiting on an I/O completion timer). Real-time tasks
+    /// and tasks parked in `Recv` do not count.
+    fn has_pending_work(&self) -> bool {
+        self.cpus.iter().any(|cpu| cpu.running_task.is_some() || cpu.has_ready_tasks())
+            || !self.injector.is_empty()
+            || !self.blocked.is_empty()
+            || !self.pending_signals.is_empty()
+            || self.timer_wheel.buckets.iter().any(|bucket| !bucket.is_empty())
+    }
+
+    /// Executes one time slice on every core (one simulated "clock tick" of
+    /// the whole SMP system).
     fn tick(&mut self) -> bool {
         self.ticks += 1;
         println!("\n--- TICK {} ---", self.ticks);
 
-        // Every 3 ticks, force a schedule (preemption)
-        if self.ticks % 3 == 0 {
-            self.schedule();
-        }
+        self.fire_due_signals();
+        self.wake_sleeping_tasks();
+        self.release_due_rt_tasks();
+        self.check_deadline_misses();
 
-        // Check if there is a task to run
-        if let Some(ref mut task) = self.running_task {
-            // Simulate task execution progress
-            task.program_counter += 1;
-            println!("[CPU] Running: {}. PC: {}", task.id, task.program_counter);
+        for cpu_idx in 0..self.cpus.len() {
+            self.cpus[cpu_idx].maybe_priority_boost(self.ticks);
 
-            // Simulate task's "program" logic and potential kernel calls
-            let kernel_call = self.simulate_task_logic(task.id, task.program_counter);
+            // Under EDF, a ready real-time task preempts a running
+            // best-effort task; the preempted task resumes first at its level.
+            let rt_preempts = self.sched_policy == SchedPolicy::Edf
+                && !self.cpus[cpu_idx].rt_queue.is_empty()
+                && self.cpus[cpu_idx].running_task.as_ref().is_some_and(|t| t.deadline.is_none());
+            if rt_preempts {
+                let mut task = self.cpus[cpu_idx].running_task.take().unwrap();
+                task.state = ProcessState::Ready;
+                println!("[SCHEDULER] CPU {}: RT task ready, preempting {}.", cpu_idx, task.id);
+                self.cpus[cpu_idx].run_queues[task.level as usize].push_front(task);
+            }
 
-            if let Some(call) = kernel_call {
-                self.handle_kernel_call(call);
+            if self.cpus[cpu_idx].running_task.is_none() {
+                self.dispatch_next(cpu_idx);
             }
 
-        } else {
-            // If the ready queue is empty and no task is running, the simulation is done
-            if self.ready_queue.is_empty() {
-                println!("[KERNEL] All tasks completed. Shutting down.");
-                return false; // Stop the simulation
+            if self.cpus[cpu_idx].running_task.is_some() {
+                self.run_cpu_slice(cpu_idx);
             }
-            // If no task is running but the queue isn't empty, schedule immediately
-            self.schedule();
+        }
+
+        if !self.has_pending_work() {
+            println!("[KERNEL] All tasks completed across {} CPU(s). Shutting down.", self.cpus.len());
+            return false; // Stop the simulation
         }
 
         true // Continue simulation
     }
 
+    /// Runs one tick's worth of work for whichever task `cpu_idx` has dispatched.
+    fn run_cpu_slice(&mut self, cpu_idx: usize) {
+        let (task_id, pc, slice_expired) = {
+            let task = self.cpus[cpu_idx].running_task.as_mut().unwrap();
+            task.program_counter += 1;
+            task.slice_remaining = task.slice_remaining.saturating_sub(1);
+            (task.id, task.program_counter, task.slice_remaining == 0)
+        };
+        println!("[CPU {}] Running: {}. PC: {}", cpu_idx, task_id, pc);
+
+        if let Some(pcb) = self.process_table.get_mut(&task_id) {
+            pcb.cpu_ticks += 1;
+        }
+
+        // Simulate task's "program" logic and potential kernel calls
+        let kernel_call = self.simulate_task_logic(task_id, pc);
+
+        if let Some(call) = kernel_call {
+            self.handle_kernel_call(cpu_idx, call);
+        }
+
+        // If the same task is still running and its slice ran out, preempt
+        // and demote it. A kernel call above may already have moved it on.
+        if slice_expired {
+            if let Some(ref task) = self.cpus[cpu_idx].running_task {
+                if task.id == task_id && task.state == ProcessState::Running {
+                    self.schedule(cpu_idx, true);
+                }
+            }
+        }
+    }
+
     /// Simulates a task's internal logic and determines if it makes a kernel call.
     fn simulate_task_logic(&self, task_id: u32, pc: u32) -> Option<KernelCall> {
         match task_id {
             // Task 1: Runs for a while then exits
             1 => match pc {
                 5 => Some(KernelCall::Print(format!("Task {} is halfway!", task_id))),
+                7 => Some(KernelCall::Sleep(3)),
                 10 => Some(KernelCall::Exit),
                 _ => None,
             },
-            // Task 2: Runs, yields, and runs more, then exits
+            // Task 2: Runs, yields, runs more, produces a message for Task 3,
+            // then exits.
             2 => match pc {
                 3 => Some(KernelCall::Yield),
                 8 => Some(KernelCall::Print(format!("Task {} is doing work.", task_id))),
+                10 => Some(KernelCall::Send(3, format!("Hello from Task {}!", task_id))),
                 12 => Some(KernelCall::Exit),
                 _ => None,
             },
-            // Task 3: Runs a bit, then blocks (simulating I/O wait)
+            // Task 3: Runs a bit, then blocks on a simulated disk read, then
+            // consumes the message Task 2 produces.
             3 => match pc {
-                4 => Some(KernelCall::Block),
+                4 => Some(KernelCall::Block(DISK_IO_EVENT)),
+                6 => Some(KernelCall::Recv),
                 _ => None,
             },
+            // Task 4: A periodic real-time task. Does its period's work in
+            // 2 ticks, then exits the period (re-released by the kernel).
+            4 => match pc % 2 {
+                1 => Some(KernelCall::Print(format!("Task {} RT cycle tick.", task_id))),
+                0 => Some(KernelCall::Exit),
+                _ => unreachable!(),
+            },
             _ => None,
         }
     }
 
-    /// Handles a request from a user process to the kernel.
-    fn handle_kernel_call(&mut self, call: KernelCall) {
+    /// Handles a request from a user process running on `cpu_idx` to the kernel.
+    fn handle_kernel_call(&mut self, cpu_idx: usize, call: KernelCall) {
         match call {
             KernelCall::Yield => {
-                println!("[KERNEL] Task requested a Yield.");
-                self.schedule();
+                if let Some(task) = self.cpus[cpu_idx].running_task.as_ref() {
+                    if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                        pcb.voluntary_yields += 1;
+                    }
+                }
+                println!("[KERNEL] CPU {}: task requested a Yield.", cpu_idx);
+                self.schedule(cpu_idx, false);
             }
             KernelCall::Exit => {
-                if let Some(mut task) = self.running_task.take() {
-                    task.state = ProcessState::Exited;
-                    println!("[KERNEL] Task {} EXITED.", task.id);
+                if let Some(mut task) = self.cpus[cpu_idx].running_task.take() {
+                    if let Some(period) = task.period {
+                        // Periodic RT task: this "exit" just marks the end of
+                        // the current period. It sleeps until its next
+                        // release instead of immediately competing for the
+                        // CPU again, leaving slack for non-RT tasks.
+                        task.state = ProcessState::Ready;
+                        task.program_counter = 0;
+                        let release_at = task.deadline.unwrap_or(self.ticks);
+                        task.deadline = Some(release_at + period);
+                        if release_at <= self.ticks {
+                            // Already past the release point (ran long); go
+                            // straight back onto the EDF queue.
+                            self.cpus[cpu_idx].rt_queue.push_back(task);
+                        } else {
+                            println!(
+                                "[KERNEL] RT task {} completed its period; sleeping until release at tick {}.",
+                                task.id, release_at
+                            );
+                            self.rt_releases.push((release_at, cpu_idx, task));
+                        }
+                    } else {
+                        task.state = ProcessState::Exited;
+                        if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                            pcb.exit_tick = Some(self.ticks);
+                        }
+                        println!("[KERNEL] Task {} EXITED on CPU {}.", task.id, cpu_idx);
+                    }
                 }
             }
             KernelCall::Print(msg) => {
-                if let Some(ref task) = self.running_task {
+                if let Some(ref task) = self.cpus[cpu_idx].running_task {
                     println!("[KERNEL/OUT] Task {} says: {}", task.id, msg);
                 }
             }
-            KernelCall::Block => {
-                if let Some(mut task) = self.running_task.take() {
+            KernelCall::Block(event_id) => {
+                if let Some(mut task) = self.cpus[cpu_idx].running_task.take() {
                     task.state = ProcessState::Blocked;
-                    println!("[KERNEL] Task {} BLOCKED. Requires a new schedule.", task.id);
-                    // In a real OS, a separate mechanism would unblock it. Here, it's just removed.
+                    if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                        pcb.blocked_since = Some(self.ticks);
+                    }
+                    println!(
+                        "[KERNEL] Task {} BLOCKED on event {} (CPU {}).",
+                        task.id, event_id, cpu_idx
+                    );
+                    self.blocked.entry(event_id).or_default().push(task);
                 }
-                self.schedule();
+                let fire_at = self.ticks + IO_COMPLETION_DELAY;
+                self.pending_signals.push((fire_at, event_id));
+                println!(
+                    "[KERNEL] Event {} scheduled to complete at tick {}.",
+                    event_id, fire_at
+                );
+                self.dispatch_next(cpu_idx);
             }
+            KernelCall::Signal(event_id) => {
+                self.complete_event(event_id);
+            }
+            KernelCall::Sleep(duration) => {
+                if let Some(mut task) = self.cpus[cpu_idx].running_task.take() {
+                    task.state = ProcessState::Blocked;
+                    if let Some(pcb) = self.process_table.get_mut(&task.id) {
+                        pcb.blocked_since = Some(self.ticks);
+                    }
+                    let wake_at = self.ticks + duration;
+                    println!(
+                        "[KERNEL] Task {} SLEEPING for {} tick(s) (CPU {}), wakes at tick {}.",
+                        task.id, duration, cpu_idx, wake_at
+                    );
+                    self.timer_wheel.schedule(wake_at, cpu_idx, task);
+                }
+                self.dispatch_next(cpu_idx);
+            }
+            KernelCall::Send(dest, msg) => {
+                if let Some(task) = self.cpus[cpu_idx].running_task.as_ref() {
+                    println!("[KERNEL] Task {} SENDS to Task {}: \"{}\".", task.id, dest, msg);
+                }
+                self.mailboxes.entry(dest).or_default().push_back(msg);
+
+                if let Some(mut task) = self.recv_waiters.remove(&dest) {
+                    let delivered = self.mailboxes.get_mut(&dest).unwrap().pop_front().unwrap();
+                    task.state = ProcessState::Ready;
+                    reset_to_top_level(&mut task);
+                    if let Some(pcb) = self.process_table.get_mut(&dest) {
+                        if let Some(since) = pcb.blocked_since.take() {
+                            pcb.ticks_blocked += self.ticks.saturating_sub(since);
+                        }
+                    }
+                    println!(
+                        "[KERNEL] Task {} woken from Recv with message: \"{}\".",
+                        dest, delivered
+                    );
+                    self.injector.push_back(task);
+                }
+            }
+            KernelCall::Recv => {
+                if let Some(task) = self.cpus[cpu_idx].running_task.as_ref() {
+                    let pid = task.id;
+                    let msg = self.mailboxes.get_mut(&pid).and_then(|mbox| mbox.pop_front());
+                    match msg {
+                        Some(msg) => {
+                            println!("[KERNEL] Task {} RECV got message: \"{}\".", pid, msg);
+                        }
+                        None => {
+                            let mut task = self.cpus[cpu_idx].running_task.take().unwrap();
+                            task.state = ProcessState::Blocked;
+                            if let Some(pcb) = self.process_table.get_mut(&pid) {
+                                pcb.blocked_since = Some(self.ticks);
+                            }
+                            println!(
+                                "[KERNEL] Task {} BLOCKED on Recv (CPU {}), mailbox empty.",
+                                pid, cpu_idx
+                            );
+                            self.recv_waiters.insert(pid, task);
+                            self.dispatch_next(cpu_idx);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Prints a `ps`-style accounting table covering every task ever
+    /// spawned, using the current tick as the "as of now" point for tasks
+    /// that haven't exited yet.
+    fn print_accounting_table(&self) {
+        println!("\n--- Process Accounting Table ---");
+        println!(
+            "{:<5}{:<16}{:<10}{:<8}{:<8}{:<10}{:<12}{:<8}",
+            "PID", "NAME", "CPU_TCKS", "SCHED#", "YIELDS", "BLK_TCKS", "TURNAROUND", "WAIT"
+        );
+        let mut pids: Vec<&u32> = self.process_table.keys().collect();
+        pids.sort();
+        for pid in pids {
+            let pcb = &self.process_table[pid];
+            let end_tick = pcb.exit_tick.unwrap_or(self.ticks);
+            let turnaround = end_tick.saturating_sub(pcb.spawn_tick);
+            let wait = turnaround.saturating_sub(pcb.cpu_ticks);
+            println!(
+                "{:<5}{:<16}{:<10}{:<8}{:<8}{:<10}{:<12}{:<8}",
+                pcb.pid,
+                pcb.name,
+                pcb.cpu_ticks,
+                pcb.times_scheduled,
+                pcb.voluntary_yields,
+                pcb.ticks_blocked,
+                turnaround,
+                wait
+            );
         }
     }
 }
 
 fn main() {
     println!("--- Kernel Simulation Start ---");
-    let mut kernel = Kernel::new();
+    let mut kernel = Kernel::new(NUM_CPUS, SchedPolicy::Edf);
 
     // Spawn initial tasks
     kernel.spawn_task("Init_Task", 10);
     kernel.spawn_task("WebApp_Worker", 5);
     kernel.spawn_task("File_IO_Task", 8);
-
-    // Initial schedule to get the first task running
-    kernel.schedule();
+    kernel.spawn_rt_task("RT_Sensor_Poll", 15, 5);
 
     // Run the main simulation loop for a maximum of 20 ticks
     while kernel.tick() && kernel.ticks < 20 {
@@ -222,14 +990,63 @@ fn main() {
     println!("\n--- Simulation End ---");
     println!("Total Ticks: {}", kernel.ticks);
 
-    // Final state of tasks (only showing what's left in the queue/running)
+    // Final state of tasks (only showing what's left in the queues/running)
     println!("\n--- Final Task State ---");
-    if let Some(task) = kernel.running_task.as_ref() {
-        println!("{}", task);
+    for cpu in kernel.cpus.iter() {
+        if let Some(task) = cpu.running_task.as_ref() {
+            println!("{}", task);
+        }
+        for queue in cpu.run_queues.iter() {
+            for task in queue.iter() {
+                println!("{}", task);
+            }
+        }
+        for task in cpu.rt_queue.iter() {
+            println!("{}", task);
+        }
     }
-    for task in kernel.ready_queue.iter() {
+    for task in kernel.injector.iter() {
         println!("{}", task);
     }
-    // Blocked and Exited tasks are not explicitly tracked in this simple model once removed.
-    println!("\n(Note: Exited and Blocked tasks are no longer tracked in the queues.)");
-}
\ No newline at end of file
+    for waiters in kernel.blocked.values() {
+        for task in waiters.iter() {
+            println!("{}", task);
+        }
+    }
+    // Report load balance across cores
+    println!("\n--- Load Balance Report ---");
+    for cpu in kernel.cpus.iter() {
+        println!(
+            "CPU {}: steals={}, injector_pops={}, idle_ticks={}",
+            cpu.id, cpu.steals, cpu.injector_pops, cpu.idle_ticks
+        );
+    }
+
+    kernel.print_accounting_table();
+
+    println!("\nDeadline misses: {}", kernel.deadline_misses);
+
+    // Comparison run: the same workload under plain best-effort MLFQ, with
+    // RT tasks left out of the picture entirely, to show what hard
+    // real-time guarantees under Edf actually buy over Round-Robin-style
+    // fairness.
+    println!("\n--- Comparison Run: best-effort MLFQ only (RT tasks ignored) ---");
+    let mut mlfq_kernel = Kernel::new(NUM_CPUS, SchedPolicy::Mlfq);
+    mlfq_kernel.spawn_task("Init_Task", 10);
+    mlfq_kernel.spawn_task("WebApp_Worker", 5);
+    mlfq_kernel.spawn_task("File_IO_Task", 8);
+    mlfq_kernel.spawn_rt_task("RT_Sensor_Poll", 15, 5);
+
+    while mlfq_kernel.tick() && mlfq_kernel.ticks < 20 {
+        // Delay for visual separation between ticks
+        // Note: In a real kernel, this loop runs continuously at high speed.
+    }
+
+    println!("\n--- Comparison Run End ---");
+    println!("Total Ticks: {}", mlfq_kernel.ticks);
+    mlfq_kernel.print_accounting_table();
+    println!(
+        "\nDeadline misses: {} (RT tasks never dispatched under SchedPolicy::Mlfq)",
+        mlfq_kernel.deadline_misses
+    );
+}